@@ -0,0 +1,35 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use tokio::{io::AsyncWriteExt, net::TcpListener};
+
+use crate::stats::ListenerStats;
+
+/// Serve a plain-text snapshot of every listener's stats to anyone who connects, then close the
+/// connection — no request parsing, just `nc admin_addr` or `curl admin_addr`.
+pub async fn serve(bind_addr: SocketAddr, listeners: Arc<Vec<Arc<ListenerStats>>>) {
+    let tcp_listener = TcpListener::bind(bind_addr)
+        .await
+        .expect("unable to bind admin stats socket");
+
+    tracing::info!(%bind_addr, "admin stats endpoint listening");
+
+    loop {
+        let Ok((mut socket, peer)) = tcp_listener.accept().await else {
+            continue;
+        };
+
+        let listeners = listeners.clone();
+
+        tokio::spawn(async move {
+            let mut report = String::new();
+
+            for listener in listeners.iter() {
+                report.push_str(&listener.render());
+            }
+
+            if let Err(err) = socket.write_all(report.as_bytes()).await {
+                tracing::debug!(%peer, %err, "failed to write stats report");
+            }
+        });
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::filters::{
+    decrypt::Decrypt, drop_chance::DropChance, duplicate::Duplicate, encrypt::Encrypt,
+    latency::Latency, reorder::Reorder, Filter, FilterChain,
+};
+
+/// The top-level config file (e.g. `proxy.toml`): one `[[listener]]` entry per `UdpSocket` the
+/// proxy should bind.
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    pub listener: Vec<ListenerConfig>,
+    /// If set, serve a plain-text flow stats snapshot to anyone who connects to this address.
+    pub admin_addr: Option<SocketAddr>,
+}
+
+impl Config {
+    /// Load and parse a config file, panicking with context if it's missing or malformed.
+    pub fn load(path: &Path) -> Config {
+        let contents = std::fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("failed to read config file {:?}: {}", path, err));
+
+        toml::from_str(&contents)
+            .unwrap_or_else(|err| panic!("failed to parse config file {:?}: {}", path, err))
+    }
+}
+
+/// A single named listener: where it binds, where it forwards to, and the filter stack applied
+/// to each direction of traffic.
+#[derive(Debug, Deserialize)]
+pub struct ListenerConfig {
+    pub name: String,
+    pub bind_addr: SocketAddr,
+    pub upstream: Upstream,
+    #[serde(default)]
+    pub client_to_server: Vec<FilterConfig>,
+    #[serde(default)]
+    pub server_to_client: Vec<FilterConfig>,
+    /// How long a route can sit idle before it's evicted. Defaults to 60 seconds.
+    #[serde(default = "default_idle_timeout_secs")]
+    pub idle_timeout_secs: u64,
+}
+
+fn default_idle_timeout_secs() -> u64 {
+    60
+}
+
+impl ListenerConfig {
+    pub fn client_to_server_chain(&self) -> FilterChain {
+        FilterChain::new(self.client_to_server.iter().map(FilterConfig::build).collect())
+    }
+
+    pub fn server_to_client_chain(&self) -> FilterChain {
+        FilterChain::new(self.server_to_client.iter().map(FilterConfig::build).collect())
+    }
+}
+
+/// Where a listener's traffic ultimately goes, modeled the way upstreams are modeled in layer-4
+/// proxies.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Upstream {
+    /// Forward to a real server address, the proxy's original (and only) behavior.
+    Proxy { addr: SocketAddr },
+    /// Forward to a real server address over a DTLS association instead of plaintext UDP.
+    DtlsProxy {
+        addr: SocketAddr,
+        cert_path: PathBuf,
+        key_path: PathBuf,
+        /// CA bundle to verify the server's certificate against. Defaults to the system trust
+        /// store when unset.
+        #[serde(default)]
+        ca_path: Option<PathBuf>,
+        /// Skip verifying the server's certificate. Dangerous: only for testing against a
+        /// self-signed server with no CA to pin; defaults to verifying.
+        #[serde(default)]
+        insecure_skip_verify: bool,
+    },
+    /// Reflect the payload straight back to `origin` without ever touching a server socket.
+    Echo,
+    /// Drop immediately and never create a route.
+    Ban,
+}
+
+/// A single filter stage as declared in the config file.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FilterConfig {
+    Drop { drop_percent: f64 },
+    Latency { base_ms: u64, jitter_ms: u64 },
+    Reorder { reorder_percent: f64 },
+    Duplicate { dup_percent: f64 },
+    /// Encrypt payloads with a 32-byte ChaCha20-Poly1305 key, given as hex or a path to a key file.
+    Encrypt {
+        key_hex: Option<String>,
+        key_file: Option<PathBuf>,
+    },
+    /// Decrypt payloads produced by the paired `Encrypt` filter on the other end of the tunnel.
+    Decrypt {
+        key_hex: Option<String>,
+        key_file: Option<PathBuf>,
+    },
+}
+
+impl FilterConfig {
+    fn build(&self) -> Box<dyn Filter + Send + Sync> {
+        match self {
+            FilterConfig::Drop { drop_percent } => Box::new(DropChance {
+                drop_percent: *drop_percent,
+            }),
+            FilterConfig::Latency { base_ms, jitter_ms } => Box::new(Latency {
+                base_ms: *base_ms,
+                jitter_ms: *jitter_ms,
+            }),
+            FilterConfig::Reorder { reorder_percent } => Box::new(Reorder::new(*reorder_percent)),
+            FilterConfig::Duplicate { dup_percent } => Box::new(Duplicate {
+                dup_percent: *dup_percent,
+            }),
+            FilterConfig::Encrypt { key_hex, key_file } => {
+                Box::new(Encrypt::new(resolve_key(key_hex, key_file)))
+            }
+            FilterConfig::Decrypt { key_hex, key_file } => {
+                Box::new(Decrypt::new(resolve_key(key_hex, key_file)))
+            }
+        }
+    }
+}
+
+/// Resolve a tunnel key given as inline hex or a path to a key file, exactly one of which must be
+/// set.
+fn resolve_key(key_hex: &Option<String>, key_file: &Option<PathBuf>) -> [u8; 32] {
+    let hex_str = match (key_hex, key_file) {
+        (Some(key_hex), None) => key_hex.clone(),
+        (None, Some(key_file)) => std::fs::read_to_string(key_file)
+            .unwrap_or_else(|err| panic!("failed to read key file {:?}: {}", key_file, err)),
+        _ => panic!("encrypt/decrypt filter needs exactly one of key_hex or key_file"),
+    };
+
+    let bytes = hex::decode(hex_str.trim()).expect("tunnel key must be valid hex");
+
+    bytes
+        .try_into()
+        .unwrap_or_else(|bytes: Vec<u8>| {
+            panic!("tunnel key must be exactly 32 bytes, got {}", bytes.len())
+        })
+}
@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+
+use crate::Datagram;
+
+use super::Filter;
+
+const NONCE_LEN: usize = 12;
+
+/// Decrypts a payload produced by `Encrypt`: splits off the nonce, verifies the Poly1305 tag, and
+/// drops the datagram on any authentication failure so forged or corrupted packets are silently
+/// discarded instead of reaching the rest of the chain.
+pub struct Decrypt {
+    cipher: ChaCha20Poly1305,
+}
+
+impl Decrypt {
+    pub fn new(key: [u8; 32]) -> Decrypt {
+        Decrypt {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key)),
+        }
+    }
+}
+
+#[async_trait]
+impl Filter for Decrypt {
+    async fn apply(&self, mut input: Datagram) -> Vec<Datagram> {
+        if input.payload.len() < NONCE_LEN {
+            return vec![];
+        }
+
+        let (nonce_bytes, ciphertext) = input.payload.split_at(NONCE_LEN);
+
+        match self.cipher.decrypt(Nonce::from_slice(nonce_bytes), ciphertext) {
+            Ok(plaintext) => {
+                input.payload = plaintext.into();
+                vec![input]
+            }
+            Err(_) => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::filters::encrypt::Encrypt;
+
+    const KEY: [u8; 32] = [7; 32];
+
+    fn datagram(payload: &[u8]) -> Datagram {
+        Datagram {
+            payload: Bytes::copy_from_slice(payload),
+            origin: "10.0.0.1:1111".parse().unwrap(),
+            destination: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trips_through_encrypt_and_decrypt() {
+        let encrypt = Encrypt::new(KEY);
+        let decrypt = Decrypt::new(KEY);
+
+        let encrypted = encrypt.apply(datagram(b"hello upstream")).await;
+        assert_eq!(encrypted.len(), 1);
+
+        let decrypted = decrypt.apply(encrypted.into_iter().next().unwrap()).await;
+        assert_eq!(decrypted.len(), 1);
+        assert_eq!(decrypted[0].payload.as_ref(), b"hello upstream");
+    }
+
+    #[tokio::test]
+    async fn drops_datagram_with_tampered_ciphertext() {
+        let encrypt = Encrypt::new(KEY);
+        let decrypt = Decrypt::new(KEY);
+
+        let mut encrypted = encrypt
+            .apply(datagram(b"hello upstream"))
+            .await
+            .into_iter()
+            .next()
+            .unwrap();
+
+        let mut tampered = encrypted.payload.to_vec();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0xff;
+        encrypted.payload = tampered.into();
+
+        assert!(decrypt.apply(encrypted).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn drops_datagram_shorter_than_the_nonce() {
+        let decrypt = Decrypt::new(KEY);
+
+        assert!(decrypt.apply(datagram(b"short")).await.is_empty());
+    }
+}
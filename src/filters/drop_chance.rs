@@ -1,3 +1,4 @@
+use async_trait::async_trait;
 use rand::prelude::*;
 
 use crate::Datagram;
@@ -6,20 +7,21 @@ use super::Filter;
 
 #[derive(Copy, Clone)]
 pub struct DropChance {
-    pub drop_percent: f64
+    pub drop_percent: f64,
 }
 
+#[async_trait]
 impl Filter for DropChance {
     /// Roll between 0-1 via PRNG. If the roll comes back less than our configured drop percent,
-    /// return None. Otherwise pass the packet through as normal.
-    fn apply(&self, input: Datagram) -> Option<Datagram> {
+    /// drop the packet. Otherwise pass it through as normal.
+    async fn apply(&self, input: Datagram) -> Vec<Datagram> {
         let mut rng = rand::thread_rng();
         let roll: f64 = rng.gen();
 
         if roll < self.drop_percent {
-            None
+            vec![]
         } else {
-            Some(input)
+            vec![input]
         }
     }
 }
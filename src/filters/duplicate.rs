@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+use rand::prelude::*;
+
+use crate::Datagram;
+
+use super::Filter;
+
+/// Emits the same datagram twice with configurable probability, mimicking `tc netem duplicate`.
+#[derive(Copy, Clone)]
+pub struct Duplicate {
+    pub dup_percent: f64,
+}
+
+#[async_trait]
+impl Filter for Duplicate {
+    async fn apply(&self, input: Datagram) -> Vec<Datagram> {
+        let mut rng = rand::thread_rng();
+        let roll: f64 = rng.gen();
+
+        if roll < self.dup_percent {
+            vec![input.clone(), input]
+        } else {
+            vec![input]
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use rand::RngCore;
+
+use crate::Datagram;
+
+use super::Filter;
+
+/// Encrypts every datagram's payload with ChaCha20-Poly1305, producing a new payload of
+/// `nonce (12 bytes) || ciphertext || tag (16 bytes)`. Pairs with `Decrypt` on the other end of
+/// the tunnel.
+pub struct Encrypt {
+    cipher: ChaCha20Poly1305,
+}
+
+impl Encrypt {
+    pub fn new(key: [u8; 32]) -> Encrypt {
+        Encrypt {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key)),
+        }
+    }
+}
+
+#[async_trait]
+impl Filter for Encrypt {
+    async fn apply(&self, mut input: Datagram) -> Vec<Datagram> {
+        let mut nonce_bytes = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut nonce_bytes);
+
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce_bytes), input.payload.as_ref())
+            .expect("ChaCha20-Poly1305 encryption failed");
+
+        let mut payload = Vec::with_capacity(nonce_bytes.len() + ciphertext.len());
+        payload.extend_from_slice(&nonce_bytes);
+        payload.extend_from_slice(&ciphertext);
+
+        input.payload = payload.into();
+
+        vec![input]
+    }
+}
@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rand::prelude::*;
+
+use crate::Datagram;
+
+use super::Filter;
+
+/// Delays every datagram by a fixed base plus jitter drawn uniformly from `0..=jitter_ms`,
+/// mimicking `tc netem delay`.
+#[derive(Copy, Clone)]
+pub struct Latency {
+    pub base_ms: u64,
+    pub jitter_ms: u64,
+}
+
+#[async_trait]
+impl Filter for Latency {
+    async fn apply(&self, input: Datagram) -> Vec<Datagram> {
+        let jitter = if self.jitter_ms == 0 {
+            0
+        } else {
+            rand::thread_rng().gen_range(0..=self.jitter_ms)
+        };
+
+        tokio::time::sleep(Duration::from_millis(self.base_ms + jitter)).await;
+
+        vec![input]
+    }
+}
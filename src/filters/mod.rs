@@ -1,7 +1,62 @@
+use async_trait::async_trait;
+
 use crate::Datagram;
 
+/// A single stage in a `FilterChain`.
+///
+/// `apply` is async so filters can delay or reschedule a datagram (see `latency` and `reorder`)
+/// instead of only being able to inspect and drop it synchronously. A filter returns every
+/// datagram it wants to forward: none to drop it, one to pass it through (optionally held back
+/// and released on a later call), or more than one to fan it out, as `duplicate` does.
+#[async_trait]
 pub trait Filter {
-    fn apply(&self, input: Datagram) -> Option<Datagram>;
+    async fn apply(&self, input: Datagram) -> Vec<Datagram>;
+}
+
+pub mod decrypt;
+pub mod drop_chance;
+pub mod duplicate;
+pub mod encrypt;
+pub mod latency;
+pub mod reorder;
+
+/// An ordered stack of filters applied to every datagram crossing one direction of the proxy.
+///
+/// Filters run in order. A datagram dropped by one filter never reaches the rest of the stack,
+/// and a datagram duplicated by one filter has each copy run through the remaining filters
+/// independently.
+pub struct FilterChain {
+    filters: Vec<Box<dyn Filter + Send + Sync>>,
+}
+
+impl FilterChain {
+    pub fn new(filters: Vec<Box<dyn Filter + Send + Sync>>) -> FilterChain {
+        FilterChain { filters }
+    }
+
+    /// Run a datagram through every filter in sequence, returning every datagram that survived
+    /// the whole chain (possibly none, possibly more than one).
+    pub async fn apply(&self, input: Datagram) -> Vec<Datagram> {
+        let mut datagrams = vec![input];
+
+        for filter in &self.filters {
+            let mut next = Vec::with_capacity(datagrams.len());
+
+            for datagram in datagrams {
+                next.extend(filter.apply(datagram).await);
+            }
+
+            datagrams = next;
+        }
+
+        datagrams
+    }
 }
 
-pub mod drop_chance;
\ No newline at end of file
+impl Default for FilterChain {
+    fn default() -> Self {
+        FilterChain {
+            filters: Vec::new(),
+        }
+    }
+}
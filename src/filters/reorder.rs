@@ -0,0 +1,143 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use rand::prelude::*;
+use tokio::sync::Mutex;
+
+use crate::Datagram;
+
+use super::Filter;
+
+/// How long a held datagram waits for its pairing packet before being released on its own. One
+/// `Reorder` instance lives for the life of its listener, so without this a flow that sends
+/// exactly one packet that happens to get held (e.g. a port scanner) would leak that datagram's
+/// slot forever — the idle-route sweeper only reaps `Router::routes`, not filter-internal state.
+const HOLD_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Holds back a datagram with configurable probability and releases it after the next one from
+/// the same flow passes, swapping their order, mimicking `tc netem reorder`.
+///
+/// One `Reorder` instance is shared (via the listener's `FilterChain`) across every client a
+/// listener routes, so the held datagram is kept per flow (`origin`/`destination` pair) rather
+/// than in a single slot — a single slot would let one client's held datagram come back out
+/// alongside an unrelated client's datagram and get forwarded through that client's route.
+pub struct Reorder {
+    pub reorder_percent: f64,
+    held: Mutex<HashMap<(SocketAddr, Option<SocketAddr>), (Datagram, Instant)>>,
+}
+
+impl Reorder {
+    pub fn new(reorder_percent: f64) -> Reorder {
+        Reorder {
+            reorder_percent,
+            held: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl Filter for Reorder {
+    async fn apply(&self, input: Datagram) -> Vec<Datagram> {
+        let mut held = self.held.lock().await;
+        let flow = (input.origin, input.destination);
+        let now = Instant::now();
+
+        // Flush any other flow's held datagram that's been waiting longer than HOLD_TIMEOUT for
+        // a pairing packet that may never come, so `held` can't grow without bound.
+        let expired: Vec<_> = held
+            .iter()
+            .filter(|(key, (_, held_at))| **key != flow && now.duration_since(*held_at) >= HOLD_TIMEOUT)
+            .map(|(key, _)| *key)
+            .collect();
+
+        let mut released: Vec<Datagram> = expired
+            .into_iter()
+            .map(|key| held.remove(&key).expect("just collected from held").0)
+            .collect();
+
+        if let Some((previous, _)) = held.remove(&flow) {
+            released.push(input);
+            released.push(previous);
+            return released;
+        }
+
+        let roll: f64 = rand::thread_rng().gen();
+
+        if roll < self.reorder_percent {
+            held.insert(flow, (input, now));
+        } else {
+            released.push(input);
+        }
+
+        released
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    fn datagram(origin: SocketAddr, destination: SocketAddr) -> Datagram {
+        Datagram {
+            payload: Bytes::from_static(b"payload"),
+            origin,
+            destination: Some(destination),
+        }
+    }
+
+    /// Two concurrent flows through one shared `Reorder` must never cross: holding back a
+    /// datagram from flow A must not cause it to be released alongside a datagram from flow B.
+    #[tokio::test]
+    async fn does_not_mix_held_datagrams_across_flows() {
+        let reorder = Reorder::new(1.0);
+        let server: SocketAddr = "10.0.0.1:9000".parse().unwrap();
+        let client_a: SocketAddr = "10.0.0.2:1111".parse().unwrap();
+        let client_b: SocketAddr = "10.0.0.3:2222".parse().unwrap();
+
+        assert!(reorder.apply(datagram(client_a, server)).await.is_empty());
+        assert!(reorder.apply(datagram(client_b, server)).await.is_empty());
+
+        let released = reorder.apply(datagram(client_a, server)).await;
+        assert_eq!(released.len(), 2);
+        assert!(released.iter().all(|d| d.origin == client_a));
+
+        let released = reorder.apply(datagram(client_b, server)).await;
+        assert_eq!(released.len(), 2);
+        assert!(released.iter().all(|d| d.origin == client_b));
+    }
+
+    /// A held datagram whose pairing packet never arrives (e.g. a client that sends one packet
+    /// and disappears) must eventually be released instead of leaking its slot in `held` forever.
+    #[tokio::test]
+    async fn releases_held_datagram_after_timeout_instead_of_leaking_it() {
+        let reorder = Reorder {
+            reorder_percent: 1.0,
+            held: Mutex::new(HashMap::new()),
+        };
+        let server: SocketAddr = "10.0.0.1:9000".parse().unwrap();
+        let scanner: SocketAddr = "10.0.0.2:1111".parse().unwrap();
+        let other: SocketAddr = "10.0.0.3:2222".parse().unwrap();
+
+        assert!(reorder.apply(datagram(scanner, server)).await.is_empty());
+        {
+            let mut held = reorder.held.lock().await;
+            let (_, held_at) = held.get_mut(&(scanner, Some(server))).unwrap();
+            *held_at = Instant::now() - HOLD_TIMEOUT;
+        }
+
+        // `other`'s own datagram is held (reorder_percent is 1.0), but `scanner`'s stale entry
+        // must be flushed out alongside it rather than sitting in `held` forever.
+        let released = reorder.apply(datagram(other, server)).await;
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].origin, scanner);
+
+        assert!(reorder.held.lock().await.contains_key(&(other, Some(server))));
+        assert!(!reorder.held.lock().await.contains_key(&(scanner, Some(server))));
+    }
+}
@@ -1,7 +1,9 @@
 use std::{
     collections::{hash_map::Entry, HashMap},
-    net::SocketAddr,
-    sync::Arc,
+    net::{SocketAddr, UdpSocket as StdUdpSocket},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 use bytes::Bytes;
@@ -13,6 +15,21 @@ use tokio::{
     task::JoinHandle,
 };
 
+use config::{Config, Upstream};
+use filters::FilterChain;
+use stats::{FlowStat, ListenerStats};
+use transport::{
+    dtls::{DtlsConfig, DtlsTransport},
+    udp::UdpTransport,
+    PacketTransport,
+};
+
+mod admin;
+mod config;
+mod filters;
+mod stats;
+mod transport;
+
 pub const BUFFER_SIZE: usize = 1500;
 
 #[derive(Debug, Clone)]
@@ -36,23 +53,33 @@ struct ChanneledSocket {
 }
 
 impl ChanneledSocket {
-    /// Create a new ChanneledSocket, injecting its UdpSocket and a sender that determines where it routes traffic to.
-    async fn new(socket: UdpSocket, sender: Sender<Datagram>) -> ChanneledSocket {
-        let socket = Arc::new(socket);
+    /// Create a new ChanneledSocket, injecting its transport, a sender that determines where it
+    /// routes traffic to, and the flow stats to record every send/recv into. Generic over
+    /// `PacketTransport` so the same plumbing carries a plain `UdpTransport` or a `DtlsTransport`
+    /// for the upstream leg.
+    async fn new<T: PacketTransport + Send + Sync + 'static>(
+        transport: T,
+        sender: Sender<Datagram>,
+        stats: Arc<FlowStat>,
+    ) -> ChanneledSocket {
+        let transport = Arc::new(transport);
         let (producer, mut receiver) = channel::<Datagram>(100);
 
-        // receiver recv -> socket send
-        let send_socket = socket.clone();
+        // receiver recv -> transport send
+        let send_transport = transport.clone();
+        let send_stats = stats.clone();
         let _socket_send = tokio::spawn(async move {
             loop {
                 if let Some(message) = receiver.recv().await {
-                    println!("sending {:?} to {:?}", &message.payload, message.destination);
+                    tracing::trace!(destination = ?message.destination, "sending datagram");
 
                     if let Some(destination) = message.destination {
-                        send_socket
+                        send_transport
                             .send_to(&message.payload, destination)
                             .await
-                            .expect("failed to send on socket");
+                            .expect("failed to send on transport");
+
+                        send_stats.record_tx(message.payload.len());
                     } else {
                         unreachable!("message should always have a destination by now");
                     }
@@ -60,13 +87,14 @@ impl ChanneledSocket {
             }
         });
 
-        let recv_socket = socket.clone();
+        let recv_transport = transport.clone();
         let _socket_recv = tokio::spawn(async move {
             loop {
                 let mut data = [0; BUFFER_SIZE];
 
-                if let Ok((len, origin)) = recv_socket.recv_from(&mut data[..]).await {
-                    println!("received {:?} from {}", &data[..len], origin);
+                if let Ok((len, origin)) = recv_transport.recv_from(&mut data[..]).await {
+                    tracing::trace!(%origin, len, "received datagram");
+                    stats.record_rx(len);
 
                     let bytes = Bytes::copy_from_slice(&data[..len]);
                     let datagram = Datagram {
@@ -75,7 +103,6 @@ impl ChanneledSocket {
                         destination: None,
                     };
 
-                    println!("Sending {:?} on channel", datagram);
                     sender
                         .send(datagram)
                         .await
@@ -97,83 +124,244 @@ impl ChanneledSocket {
     }
 }
 
+impl Drop for ChanneledSocket {
+    fn drop(&mut self) {
+        self._socket_send.abort();
+        self._socket_recv.abort();
+    }
+}
+
 struct Router;
 
 struct Route {
     channeled_socket: ChanneledSocket,
     _recv_task: JoinHandle<()>,
+    last_activity: Arc<Mutex<Instant>>,
+    stats: Arc<FlowStat>,
 }
 
+impl Drop for Route {
+    fn drop(&mut self) {
+        self._recv_task.abort();
+    }
+}
+
+/// How often the idle sweeper checks routes for eviction, independent of the configured timeout.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
 impl Router {
     fn new(
-        server_addr: SocketAddr,
+        upstream: Upstream,
         client_sender: Sender<Datagram>,
         mut client_receiver: Receiver<Datagram>,
+        client_to_server: FilterChain,
+        server_to_client: FilterChain,
+        idle_timeout: Duration,
+        listener_stats: Arc<ListenerStats>,
     ) -> JoinHandle<()> {
         let mut routes = HashMap::new();
+        let client_to_server = Arc::new(client_to_server);
+        let server_to_client = Arc::new(server_to_client);
+        let mut sweep_interval = tokio::time::interval(SWEEP_INTERVAL);
 
         let router = tokio::spawn(async move {
             loop {
-                if let Some(message) = client_receiver.recv().await {
-                    println!("router received message {:?}", message);
-
-                    if let Entry::Vacant(_) = routes.entry(message.origin) {
-                        let proxy_socket = UdpSocket::bind(("127.0.0.1", 0))
-                            .await
-                            .expect("unable to bind proxy socket");
-
-                        proxy_socket
-                            .connect(server_addr)
-                            .await
-                            .expect("failed to connect proxy socket to server address");
-
-                        println!(
-                            "proxy socket created on port {:?}",
-                            proxy_socket.local_addr()
-                        );
-
-                        let (router_sender, mut proxy_receiver) = channel::<Datagram>(100);
-                        let channeled_socket =
-                            ChanneledSocket::new(proxy_socket, router_sender).await;
+                tokio::select! {
+                    message = client_receiver.recv() => {
+                        let Some(message) = message else {
+                            continue;
+                        };
 
-                        let client_sender_clone = client_sender.clone();
+                        tracing::trace!(?message, "router received message");
 
-                        let destination = message.origin.clone();
+                        let server_addr = match &upstream {
+                            Upstream::Ban => {
+                                listener_stats.global.record_drop();
+                                continue;
+                            }
+                            Upstream::Echo => {
+                                let reply = message.clone().set_destination(message.origin);
+                                let server_to_client = server_to_client.clone();
+                                let global_stats = listener_stats.global.clone();
+                                let client_sender = client_sender.clone();
+
+                                // Spawned so a Latency filter's sleep only delays this one
+                                // datagram instead of blocking the router loop for every client.
+                                tokio::spawn(async move {
+                                    let replies = server_to_client.apply(reply).await;
+
+                                    if replies.is_empty() {
+                                        global_stats.record_drop();
+                                    }
+
+                                    for reply in replies {
+                                        client_sender
+                                            .send(reply)
+                                            .await
+                                            .expect("failed to send echoed reply to client sender");
+                                    }
+                                });
+
+                                continue;
+                            }
+                            Upstream::Proxy { addr } => *addr,
+                            Upstream::DtlsProxy { addr, .. } => *addr,
+                        };
 
-                        let _recv_task = tokio::spawn(async move {
-                            loop {
-                                if let Some(received) = proxy_receiver.recv().await {
-                                    println!("Received message from proxy socket: {:?}", received);
+                        if let Entry::Vacant(_) = routes.entry(message.origin) {
+                            let (router_sender, mut proxy_receiver) = channel::<Datagram>(100);
+                            let route_stats = listener_stats.register_route(message.origin);
 
-                                    let received = received.set_destination(destination);
-                                    client_sender_clone
-                                        .send(received)
+                            let channeled_socket = match &upstream {
+                                Upstream::Proxy { addr } => {
+                                    let proxy_socket = UdpSocket::bind(("127.0.0.1", 0))
                                         .await
-                                        .expect("failed to send to client sender");
+                                        .expect("unable to bind proxy socket");
+
+                                    proxy_socket.connect(*addr).await.expect(
+                                        "failed to connect proxy socket to server address",
+                                    );
+
+                                    tracing::debug!(
+                                        local_addr = ?proxy_socket.local_addr(),
+                                        "proxy socket created"
+                                    );
+
+                                    ChanneledSocket::new(
+                                        UdpTransport::new(proxy_socket),
+                                        router_sender,
+                                        route_stats.clone(),
+                                    )
+                                    .await
+                                }
+                                Upstream::DtlsProxy {
+                                    addr,
+                                    cert_path,
+                                    key_path,
+                                    ca_path,
+                                    insecure_skip_verify,
+                                } => {
+                                    let dtls_socket = StdUdpSocket::bind("127.0.0.1:0")
+                                        .expect("unable to bind DTLS socket");
+
+                                    let dtls_config = DtlsConfig {
+                                        cert_path: cert_path.clone(),
+                                        key_path: key_path.clone(),
+                                        destination: *addr,
+                                        ca_path: ca_path.clone(),
+                                        insecure_skip_verify: *insecure_skip_verify,
+                                    };
+
+                                    let transport = DtlsTransport::connect(dtls_socket, &dtls_config)
+                                        .expect("DTLS handshake failed");
+
+                                    tracing::debug!(%addr, "DTLS association established");
+
+                                    ChanneledSocket::new(transport, router_sender, route_stats.clone())
+                                        .await
+                                }
+                                Upstream::Echo | Upstream::Ban => {
+                                    unreachable!("Echo/Ban never create a route")
+                                }
+                            };
+
+                            let client_sender_clone = client_sender.clone();
+
+                            let destination = message.origin.clone();
+                            let server_to_client = server_to_client.clone();
+                            let last_activity = Arc::new(Mutex::new(Instant::now()));
+                            let recv_task_activity = last_activity.clone();
+                            let recv_task_stats = route_stats.clone();
+                            let recv_task_global_stats = listener_stats.global.clone();
+
+                            let _recv_task = tokio::spawn(async move {
+                                loop {
+                                    if let Some(received) = proxy_receiver.recv().await {
+                                        tracing::trace!(?received, "received message from proxy socket");
+
+                                        let received = received.set_destination(destination);
+                                        let replies = server_to_client.apply(received).await;
+
+                                        if replies.is_empty() {
+                                            recv_task_stats.record_drop();
+                                            recv_task_global_stats.record_drop();
+                                        }
+
+                                        for received in replies {
+                                            client_sender_clone
+                                                .send(received)
+                                                .await
+                                                .expect("failed to send to client sender");
+                                        }
+
+                                        *recv_task_activity.lock().unwrap() = Instant::now();
+                                    }
+                                }
+                            });
+
+                            let route = Route {
+                                channeled_socket,
+                                _recv_task,
+                                last_activity,
+                                stats: route_stats,
+                            };
+
+                            routes.insert(message.origin, route);
+                        }
+
+                        // forward to server
+                        if let Some(route) = routes.get(&message.origin) {
+                            let sender = route.channeled_socket.get_input_sender();
+                            let stats = route.stats.clone();
+                            let global_stats = listener_stats.global.clone();
+                            *route.last_activity.lock().unwrap() = Instant::now();
+
+                            let client_to_server = client_to_server.clone();
+                            let message = message.set_destination(server_addr);
+
+                            // Spawned so a Latency filter's sleep only delays this one datagram
+                            // instead of blocking the router loop (and therefore every other
+                            // client and the idle sweeper) for the whole delay.
+                            tokio::spawn(async move {
+                                let messages = client_to_server.apply(message).await;
+
+                                if messages.is_empty() {
+                                    stats.record_drop();
+                                    global_stats.record_drop();
                                 }
-                            }
-                        });
-
-                        let route = Route {
-                            channeled_socket,
-                            _recv_task,
-                        };
 
-                        routes.insert(message.origin, route);
+                                for message in messages {
+                                    tracing::trace!("sending message to proxy socket");
+
+                                    // The route this datagram was headed for may have been
+                                    // evicted by the idle sweeper while it sat in a filter (e.g.
+                                    // a Latency sleep); its channeled socket and receiver are
+                                    // gone in that case, so there's nowhere left to send it.
+                                    if sender.send(message).await.is_err() {
+                                        tracing::debug!(
+                                            "dropping datagram: its route was evicted before it could be forwarded"
+                                        );
+                                        stats.record_drop();
+                                        global_stats.record_drop();
+                                    }
+                                }
+                            });
+                        }
                     }
-
-                    // forward to server
-                    if let Some(route) = routes.get(&message.origin) {
-                        println!("sending message to proxy socket");
-
-                        let message = message.set_destination(server_addr);
-
-                        route
-                            .channeled_socket
-                            .get_input_sender()
-                            .send(message)
-                            .await
-                            .expect("failed to send message from router to proxy socket");
+                    _ = sweep_interval.tick() => {
+                        let now = Instant::now();
+                        let expired: Vec<SocketAddr> = routes
+                            .iter()
+                            .filter(|(_, route)| {
+                                now.duration_since(*route.last_activity.lock().unwrap()) >= idle_timeout
+                            })
+                            .map(|(origin, _)| *origin)
+                            .collect();
+
+                        for origin in expired {
+                            routes.remove(&origin);
+                            listener_stats.remove_route(&origin);
+                        }
                     }
                 }
             }
@@ -183,27 +371,77 @@ impl Router {
     }
 }
 
+/// How often the background task logs each listener's stats snapshot.
+const STATS_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
+    tracing_subscriber::fmt::init();
+
     let args = Args::parse();
+    let config = Config::load(&args.config);
+
+    tracing::info!("starting up");
+
+    let mut routers = Vec::new();
+    let mut listener_stats = Vec::new();
+
+    for listener in config.listener {
+        let client_socket = UdpSocket::bind(listener.bind_addr)
+            .await
+            .expect("unable to bind client socket");
 
-    println!("starting up");
+        let (router_sender, client_receiver) = channel::<Datagram>(100);
+        let stats = ListenerStats::new(listener.name.clone());
 
-    let client_socket = UdpSocket::bind(("0.0.0.0", args.listen_port))
-        .await
-        .expect("unable to bind client socket");
+        let client_socket = ChanneledSocket::new(
+            UdpTransport::new(client_socket),
+            router_sender.clone(),
+            stats.global.clone(),
+        )
+        .await;
 
-    let (router_sender, client_receiver) = channel::<Datagram>(100);
+        tracing::info!(name = %listener.name, bind_addr = %listener.bind_addr, "listener bound");
 
-    let client_socket = ChanneledSocket::new(client_socket, router_sender.clone()).await;
+        let client_to_server_chain = listener.client_to_server_chain();
+        let server_to_client_chain = listener.server_to_client_chain();
 
-    let router = Router::new(
-        args.server_addr,
-        client_socket.get_input_sender(),
-        client_receiver,
-    );
+        let router = Router::new(
+            listener.upstream,
+            client_socket.get_input_sender(),
+            client_receiver,
+            client_to_server_chain,
+            server_to_client_chain,
+            Duration::from_secs(listener.idle_timeout_secs),
+            stats.clone(),
+        );
 
-    let _join = tokio::join!(router);
+        routers.push(router);
+        listener_stats.push(stats);
+    }
+
+    let listener_stats = Arc::new(listener_stats);
+
+    let stats_for_logging = listener_stats.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(STATS_LOG_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            for stats in stats_for_logging.iter() {
+                tracing::info!("{}", stats.render());
+            }
+        }
+    });
+
+    if let Some(admin_addr) = config.admin_addr {
+        tokio::spawn(admin::serve(admin_addr, listener_stats));
+    }
+
+    for router in routers {
+        router.await.expect("router task panicked");
+    }
 
     Ok(())
 }
@@ -211,15 +449,7 @@ async fn main() -> std::io::Result<()> {
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// The port to listen for client traffic on
-    #[arg(long)]
-    listen_port: u16,
-
-    /// The address to forward client traffic to
+    /// Path to the TOML config file declaring listeners, upstreams, and filter stacks.
     #[arg(long)]
-    server_addr: SocketAddr,
-    // /// A value between 0 and 1 representing the percentage chance to drop any given packet.
-    // /// 0 will never drop a packet, 1 will always drop a packet.
-    // #[arg(long)]
-    // drop_percent: Option<f64>,
+    config: PathBuf,
 }
@@ -0,0 +1,124 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Atomic byte/packet counters for one flow — a single route, or a whole listener.
+#[derive(Default)]
+pub struct FlowStat {
+    tx_bytes: AtomicU64,
+    tx_packets: AtomicU64,
+    rx_bytes: AtomicU64,
+    rx_packets: AtomicU64,
+    drops: AtomicU64,
+}
+
+impl FlowStat {
+    pub fn record_tx(&self, bytes: usize) {
+        self.tx_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.tx_packets.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_rx(&self, bytes: usize) {
+        self.rx_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.rx_packets.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_drop(&self) {
+        self.drops.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> FlowStatSnapshot {
+        FlowStatSnapshot {
+            tx_bytes: self.tx_bytes.load(Ordering::Relaxed),
+            tx_packets: self.tx_packets.load(Ordering::Relaxed),
+            rx_bytes: self.rx_bytes.load(Ordering::Relaxed),
+            rx_packets: self.rx_packets.load(Ordering::Relaxed),
+            drops: self.drops.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlowStatSnapshot {
+    pub tx_bytes: u64,
+    pub tx_packets: u64,
+    pub rx_bytes: u64,
+    pub rx_packets: u64,
+    pub drops: u64,
+}
+
+/// Every flow counter for one listener: a global total plus one `FlowStat` per active route,
+/// keyed by the client's origin address.
+pub struct ListenerStats {
+    pub name: String,
+    pub global: Arc<FlowStat>,
+    routes: Mutex<HashMap<SocketAddr, Arc<FlowStat>>>,
+}
+
+impl ListenerStats {
+    pub fn new(name: String) -> Arc<ListenerStats> {
+        Arc::new(ListenerStats {
+            name,
+            global: Arc::new(FlowStat::default()),
+            routes: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Register a new route's stats, returning the `FlowStat` the route should record into.
+    pub fn register_route(&self, origin: SocketAddr) -> Arc<FlowStat> {
+        let stats = Arc::new(FlowStat::default());
+        self.routes.lock().unwrap().insert(origin, stats.clone());
+        stats
+    }
+
+    pub fn remove_route(&self, origin: &SocketAddr) {
+        self.routes.lock().unwrap().remove(origin);
+    }
+
+    pub fn active_routes(&self) -> usize {
+        self.routes.lock().unwrap().len()
+    }
+
+    /// Render a human-readable snapshot: active route count, global totals, then a line per
+    /// origin, suitable for a log line or the admin stats endpoint.
+    pub fn render(&self) -> String {
+        let active_routes = self.active_routes();
+        let routes = self.routes.lock().unwrap();
+        let global = self.global.snapshot();
+
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "listener {}: {} active routes, {} dropped",
+            self.name, active_routes, global.drops
+        );
+        let _ = writeln!(
+            out,
+            "  total: tx={}pkts/{}B rx={}pkts/{}B",
+            global.tx_packets, global.tx_bytes, global.rx_packets, global.rx_bytes
+        );
+
+        for (origin, stats) in routes.iter() {
+            let snapshot = stats.snapshot();
+            let _ = writeln!(
+                out,
+                "  {}: tx={}pkts/{}B rx={}pkts/{}B drops={}",
+                origin,
+                snapshot.tx_packets,
+                snapshot.tx_bytes,
+                snapshot.rx_packets,
+                snapshot.rx_bytes,
+                snapshot.drops
+            );
+        }
+
+        out
+    }
+}
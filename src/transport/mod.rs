@@ -0,0 +1,14 @@
+use std::{io, net::SocketAddr};
+
+use async_trait::async_trait;
+
+/// Moves raw datagrams to and from a peer, abstracting `ChanneledSocket` over what's actually
+/// carrying them — a bare `UdpSocket` today, a DTLS association for the upstream leg.
+#[async_trait]
+pub trait PacketTransport {
+    async fn send_to(&self, buf: &[u8], destination: SocketAddr) -> io::Result<usize>;
+    async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)>;
+}
+
+pub mod dtls;
+pub mod udp;
@@ -0,0 +1,28 @@
+use std::{io, net::SocketAddr};
+
+use async_trait::async_trait;
+use tokio::net::UdpSocket;
+
+use super::PacketTransport;
+
+/// The proxy's original transport: a plain, unencrypted `UdpSocket`.
+pub struct UdpTransport {
+    socket: UdpSocket,
+}
+
+impl UdpTransport {
+    pub fn new(socket: UdpSocket) -> UdpTransport {
+        UdpTransport { socket }
+    }
+}
+
+#[async_trait]
+impl PacketTransport for UdpTransport {
+    async fn send_to(&self, buf: &[u8], destination: SocketAddr) -> io::Result<usize> {
+        self.socket.send_to(buf, destination).await
+    }
+
+    async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.socket.recv_from(buf).await
+    }
+}